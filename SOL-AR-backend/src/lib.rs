@@ -2,6 +2,10 @@ use anchor_lang::prelude::*;
 
 declare_id!("9qTuVAyYyTuoLTWSRLXmrbQycKKbAMCjwFhUV9LPSMdR");
 
+/// Max byte length budgeted for `NoteAccount::arweave_hash` / `RevisionAccount::arweave_hash`
+/// (must match the `#[max_len(..)]` on both fields).
+const ARWEAVE_HASH_MAX_LEN: usize = 64;
+
 #[program]
 pub mod elysium_program {
     use super::*;
@@ -10,57 +14,495 @@ pub mod elysium_program {
         let note_account = &mut ctx.accounts.note_account;
         note_account.owner = *ctx.accounts.user.key;
         note_account.note_id = note_id;
-        note_account.arweave_hash = arweave_hash;
-        note_account.timestamp = timestamp;
-        note_account.is_permanent = false;
+        note_account.pending_owner = None;
+        note_account.encryption = None;
+        note_account.key_epoch = 0;
+
+        init_genesis_revision(note_account, &mut ctx.accounts.genesis_revision, arweave_hash, timestamp)?;
+        Ok(())
+    }
+
+    pub fn initialize_encrypted_note(
+        ctx: Context<InitializeEncryptedNote>,
+        note_id: u64,
+        arweave_hash: String,
+        timestamp: i64,
+        scheme: u8,
+        wrapped_key_hash: [u8; 32],
+        nonce: [u8; 24],
+    ) -> Result<()> {
+        let note_account = &mut ctx.accounts.note_account;
+        note_account.owner = *ctx.accounts.user.key;
+        note_account.note_id = note_id;
+        note_account.pending_owner = None;
+        note_account.encryption = Some(EncryptionMeta {
+            scheme,
+            wrapped_key_hash,
+            nonce,
+        });
+        note_account.key_epoch = 0;
+
+        init_genesis_revision(note_account, &mut ctx.accounts.genesis_revision, arweave_hash, timestamp)?;
+        Ok(())
+    }
+
+    pub fn rotate_key(
+        ctx: Context<RotateKey>,
+        note_id: u64,
+        new_wrapped_key_hash: [u8; 32],
+        new_nonce: [u8; 24],
+    ) -> Result<()> {
+        let collaborator = ctx.accounts.collaborator.as_ref();
+        authorize(
+            &ctx.accounts.note_account,
+            ctx.accounts.user.key,
+            collaborator,
+            Role::Admin,
+        )?;
+
+        let note_account = &mut ctx.accounts.note_account;
+        require!(note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+        let encryption = note_account
+            .encryption
+            .as_mut()
+            .ok_or(ErrorCode::NoteNotEncrypted)?;
+        encryption.wrapped_key_hash = new_wrapped_key_hash;
+        encryption.nonce = new_nonce;
+        note_account.key_epoch += 1;
+
+        emit!(KeyRotated {
+            note: note_account.key(),
+            note_id,
+            key_epoch: note_account.key_epoch,
+        });
         Ok(())
     }
 
     pub fn set_permanent(ctx: Context<SetPermanent>, note_id: u64) -> Result<()> {
+        let collaborator = ctx.accounts.collaborator.as_ref();
+        authorize(
+            &ctx.accounts.note_account,
+            ctx.accounts.user.key,
+            collaborator,
+            Role::Admin,
+        )?;
+
         let note_account = &mut ctx.accounts.note_account;
         require!(note_account.note_id == note_id, ErrorCode::InvalidNoteId);
         note_account.is_permanent = true;
+
+        emit!(NotePermanent {
+            note: note_account.key(),
+            note_id,
+        });
+        Ok(())
+    }
+
+    pub fn add_revision(ctx: Context<AddRevision>, note_id: u64, arweave_hash: String, timestamp: i64) -> Result<()> {
+        let collaborator = ctx.accounts.collaborator.as_ref();
+        authorize(
+            &ctx.accounts.note_account,
+            ctx.accounts.user.key,
+            collaborator,
+            Role::Editor,
+        )?;
+
+        let note_account = &mut ctx.accounts.note_account;
+        require!(note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+        require!(!note_account.is_permanent, ErrorCode::NotePermanent);
+        require!(
+            ctx.accounts.parent_revision.key() == note_account.latest_revision,
+            ErrorCode::StaleParentRevision
+        );
+        require!(arweave_hash.len() <= ARWEAVE_HASH_MAX_LEN, ErrorCode::ArweaveHashTooLong);
+
+        let seq = note_account.revision_count;
+        let revision = &mut ctx.accounts.revision;
+        revision.note = note_account.key();
+        revision.seq = seq;
+        revision.parent = Some(ctx.accounts.parent_revision.key());
+        revision.arweave_hash = arweave_hash.clone();
+        revision.timestamp = timestamp;
+
+        note_account.latest_revision = revision.key();
+        note_account.revision_count += 1;
+
+        emit!(RevisionAdded {
+            note: note_account.key(),
+            revision: revision.key(),
+            seq,
+            arweave_hash,
+            timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn add_collaborator(ctx: Context<AddCollaborator>, note_id: u64, collaborator: Pubkey, role: u8) -> Result<()> {
+        authorize(
+            &ctx.accounts.note_account,
+            ctx.accounts.user.key,
+            ctx.accounts.admin_collaborator.as_ref(),
+            Role::Admin,
+        )?;
+        require!(ctx.accounts.note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+
+        let role = Role::try_from(role)?;
+        let collaborator_account = &mut ctx.accounts.collaborator_account;
+        collaborator_account.note = ctx.accounts.note_account.key();
+        collaborator_account.user = collaborator;
+        collaborator_account.role = role;
+        collaborator_account.added_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn remove_collaborator(ctx: Context<RemoveCollaborator>, note_id: u64, _collaborator: Pubkey) -> Result<()> {
+        authorize(
+            &ctx.accounts.note_account,
+            ctx.accounts.user.key,
+            ctx.accounts.admin_collaborator.as_ref(),
+            Role::Admin,
+        )?;
+        require!(ctx.accounts.note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+        Ok(())
+    }
+
+    // Permanence only locks the note's content (revisions), not its custody, so transfer
+    // is allowed regardless of `is_permanent`.
+    pub fn propose_transfer(ctx: Context<ProposeTransfer>, note_id: u64, new_owner: Pubkey) -> Result<()> {
+        let note_account = &mut ctx.accounts.note_account;
+        require!(note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+        require_keys_eq!(*ctx.accounts.user.key, note_account.owner, ErrorCode::Unauthorized);
+        note_account.pending_owner = Some(new_owner);
+        Ok(())
+    }
+
+    pub fn accept_transfer(ctx: Context<AcceptTransfer>, note_id: u64) -> Result<()> {
+        let note_account = &mut ctx.accounts.note_account;
+        require!(note_account.note_id == note_id, ErrorCode::InvalidNoteId);
+        require!(
+            note_account.pending_owner == Some(*ctx.accounts.new_owner.key),
+            ErrorCode::Unauthorized
+        );
+        note_account.owner = *ctx.accounts.new_owner.key;
+        note_account.pending_owner = None;
         Ok(())
     }
 }
 
+/// Shared by `initialize_note` and `initialize_encrypted_note`: seeds the genesis revision,
+/// wires it up as the note's head, and emits `NoteInitialized`.
+fn init_genesis_revision(
+    note_account: &mut Account<NoteAccount>,
+    genesis_revision: &mut Account<RevisionAccount>,
+    arweave_hash: String,
+    timestamp: i64,
+) -> Result<()> {
+    require!(arweave_hash.len() <= ARWEAVE_HASH_MAX_LEN, ErrorCode::ArweaveHashTooLong);
+
+    note_account.arweave_hash = arweave_hash.clone();
+    note_account.timestamp = timestamp;
+    note_account.is_permanent = false;
+    note_account.revision_count = 1;
+    note_account.latest_revision = genesis_revision.key();
+
+    genesis_revision.note = note_account.key();
+    genesis_revision.seq = 0;
+    genesis_revision.parent = None;
+    genesis_revision.arweave_hash = arweave_hash.clone();
+    genesis_revision.timestamp = timestamp;
+
+    emit!(NoteInitialized {
+        note: note_account.key(),
+        owner: note_account.owner,
+        note_id: note_account.note_id,
+        arweave_hash,
+        timestamp,
+    });
+    Ok(())
+}
+
+/// Checks that `signer` is the note's owner, or holds at least `required` role via `collaborator`.
+fn authorize(
+    note_account: &NoteAccount,
+    signer: &Pubkey,
+    collaborator: Option<&Account<CollaboratorAccount>>,
+    required: Role,
+) -> Result<()> {
+    if *signer == note_account.owner {
+        return Ok(());
+    }
+    let collaborator = collaborator.ok_or(ErrorCode::Unauthorized)?;
+    require_keys_eq!(collaborator.user, *signer, ErrorCode::Unauthorized);
+    require_keys_eq!(collaborator.note, note_account.key(), ErrorCode::Unauthorized);
+    require!(collaborator.role >= required, ErrorCode::InsufficientRole);
+    Ok(())
+}
+
 #[derive(Accounts)]
+#[instruction(note_id: u64)]
 pub struct InitializeNote<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 8 + 64 + 8 + 1,
+        space = 8 + NoteAccount::INIT_SPACE,
         seeds = [b"note", user.key.as_ref(), note_id.to_le_bytes().as_ref()],
         bump
     )]
     pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + RevisionAccount::INIT_SPACE,
+        seeds = [b"rev", note_account.key().as_ref(), 0u64.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub genesis_revision: Account<'info, RevisionAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct InitializeEncryptedNote<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + NoteAccount::INIT_SPACE,
+        seeds = [b"note", user.key.as_ref(), note_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + RevisionAccount::INIT_SPACE,
+        seeds = [b"rev", note_account.key().as_ref(), 0u64.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub genesis_revision: Account<'info, RevisionAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateKey<'info> {
+    #[account(mut)]
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        seeds = [b"collab", note_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub collaborator: Option<Account<'info, CollaboratorAccount>>,
+    pub user: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetPermanent<'info> {
+    #[account(mut)]
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        seeds = [b"collab", note_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub collaborator: Option<Account<'info, CollaboratorAccount>>,
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64)]
+pub struct AddRevision<'info> {
+    #[account(mut)]
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        seeds = [b"collab", note_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub collaborator: Option<Account<'info, CollaboratorAccount>>,
+    /// CHECK: only used to verify it matches `note_account.latest_revision`; contents aren't read.
+    pub parent_revision: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + RevisionAccount::INIT_SPACE,
+        seeds = [b"rev", note_account.key().as_ref(), note_account.revision_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub revision: Account<'info, RevisionAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64, collaborator: Pubkey)]
+pub struct AddCollaborator<'info> {
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        seeds = [b"collab", note_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub admin_collaborator: Option<Account<'info, CollaboratorAccount>>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + CollaboratorAccount::INIT_SPACE,
+        seeds = [b"collab", note_account.key().as_ref(), collaborator.as_ref()],
+        bump
+    )]
+    pub collaborator_account: Account<'info, CollaboratorAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(note_id: u64, collaborator: Pubkey)]
+pub struct RemoveCollaborator<'info> {
+    pub note_account: Account<'info, NoteAccount>,
+    #[account(
+        seeds = [b"collab", note_account.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub admin_collaborator: Option<Account<'info, CollaboratorAccount>>,
     #[account(
         mut,
-        seeds = [b"note", user.key.as_ref(), note_id.to_le_bytes().as_ref()],
+        close = user,
+        seeds = [b"collab", note_account.key().as_ref(), collaborator.as_ref()],
         bump
     )]
+    pub collaborator_account: Account<'info, CollaboratorAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTransfer<'info> {
+    #[account(mut)]
     pub note_account: Account<'info, NoteAccount>,
     pub user: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptTransfer<'info> {
+    #[account(mut)]
+    pub note_account: Account<'info, NoteAccount>,
+    pub new_owner: Signer<'info>,
+}
+
+#[event]
+pub struct NoteInitialized {
+    pub note: Pubkey,
+    pub owner: Pubkey,
+    pub note_id: u64,
+    pub arweave_hash: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotePermanent {
+    pub note: Pubkey,
+    pub note_id: u64,
+}
+
+#[event]
+pub struct RevisionAdded {
+    pub note: Pubkey,
+    pub revision: Pubkey,
+    pub seq: u64,
+    pub arweave_hash: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeyRotated {
+    pub note: Pubkey,
+    pub note_id: u64,
+    pub key_epoch: u64,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct NoteAccount {
     pub owner: Pubkey,
     pub note_id: u64,
+    #[max_len(64)]
     pub arweave_hash: String,
     pub timestamp: i64,
     pub is_permanent: bool,
+    pub latest_revision: Pubkey,
+    pub revision_count: u64,
+    pub pending_owner: Option<Pubkey>,
+    pub encryption: Option<EncryptionMeta>,
+    pub key_epoch: u64,
+}
+
+/// Binds a note to the current wrapped-key epoch; the chain never sees plaintext or keys,
+/// only a commitment to the off-chain wrapped data key.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct EncryptionMeta {
+    pub scheme: u8,
+    pub wrapped_key_hash: [u8; 32],
+    pub nonce: [u8; 24],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RevisionAccount {
+    pub note: Pubkey,
+    pub seq: u64,
+    pub parent: Option<Pubkey>,
+    #[max_len(64)]
+    pub arweave_hash: String,
+    pub timestamp: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CollaboratorAccount {
+    pub note: Pubkey,
+    pub user: Pubkey,
+    pub role: Role,
+    pub added_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl TryFrom<u8> for Role {
+    type Error = ErrorCode;
+
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Role::Viewer),
+            1 => Ok(Role::Editor),
+            2 => Ok(Role::Admin),
+            _ => Err(ErrorCode::InvalidRole),
+        }
+    }
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid note ID")]
     InvalidNoteId,
+    #[msg("Note is permanent and can no longer be revised")]
+    NotePermanent,
+    #[msg("Parent revision does not match the note's current head")]
+    StaleParentRevision,
+    #[msg("Arweave hash exceeds the 64-byte budget")]
+    ArweaveHashTooLong,
+    #[msg("Signer is not the note owner or an authorized collaborator")]
+    Unauthorized,
+    #[msg("Collaborator role is insufficient for this action")]
+    InsufficientRole,
+    #[msg("Role value must be 0 (Viewer), 1 (Editor), or 2 (Admin)")]
+    InvalidRole,
+    #[msg("Note has no encryption envelope to rotate")]
+    NoteNotEncrypted,
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+//! Pins the PDA derivation and `NoteAccount` layout against fixed test vectors
+//! (`tests/fixtures/*.json`) so a future field addition to `NoteAccount` or a
+//! seed change is caught here instead of corrupting on-chain rent/layout.
+//!
+//! The fixtures are flat and fully controlled by this crate, so vectors are
+//! parsed with a small hand-rolled extractor below instead of pulling in
+//! `serde`/`serde_json`/`hex` (not present in this tree, which has no
+//! Cargo.toml to add them to).
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorSerialize, Discriminator};
+use sol_ar_backend::NoteAccount;
+
+struct NoteVector {
+    user: String,
+    note_id: u64,
+    arweave_hash: String,
+    timestamp: i64,
+    expected_note_pda: String,
+    expected_note_bump: u8,
+    expected_genesis_revision_pda: String,
+    expected_genesis_revision_bump: u8,
+    expected_note_account_space: usize,
+    expected_note_account_bytes_hex: String,
+}
+
+/// Pulls `"key": <value>` out of a flat, single-line-per-field JSON object, returning the
+/// raw value text (quotes included for strings). Not a general-purpose JSON parser: it only
+/// handles the fixed layout of the checked-in fixtures under `tests/fixtures/`.
+fn field<'a>(raw: &'a str, key: &str) -> &'a str {
+    let needle = format!("\"{key}\"");
+    let after_key = &raw[raw.find(&needle).unwrap_or_else(|| panic!("missing field `{key}`")) + needle.len()..];
+    let after_colon = &after_key[after_key.find(':').unwrap() + 1..];
+    let value_start = after_colon.find(|c: char| !c.is_whitespace()).unwrap();
+    let value = &after_colon[value_start..];
+
+    if let Some(rest) = value.strip_prefix('"') {
+        &value[..rest.find('"').unwrap() + 2]
+    } else {
+        let end = value.find(|c: char| c == ',' || c == '}' || c == '\n').unwrap();
+        &value[..end]
+    }
+}
+
+fn string_field(raw: &str, key: &str) -> String {
+    let quoted = field(raw, key);
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+fn parse_vector(raw: &str) -> NoteVector {
+    NoteVector {
+        user: string_field(raw, "user"),
+        note_id: field(raw, "note_id").trim().parse().unwrap(),
+        arweave_hash: string_field(raw, "arweave_hash"),
+        timestamp: field(raw, "timestamp").trim().parse().unwrap(),
+        expected_note_pda: string_field(raw, "expected_note_pda"),
+        expected_note_bump: field(raw, "expected_note_bump").trim().parse().unwrap(),
+        expected_genesis_revision_pda: string_field(raw, "expected_genesis_revision_pda"),
+        expected_genesis_revision_bump: field(raw, "expected_genesis_revision_bump").trim().parse().unwrap(),
+        expected_note_account_space: field(raw, "expected_note_account_space").trim().parse().unwrap(),
+        expected_note_account_bytes_hex: string_field(raw, "expected_note_account_bytes_hex"),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        out.push(DIGITS[(b >> 4) as usize] as char);
+        out.push(DIGITS[(b & 0x0f) as usize] as char);
+    }
+    out
+}
+
+const FIXTURES: &[&str] = &[
+    include_str!("fixtures/basic.json"),
+    include_str!("fixtures/max_arweave_hash.json"),
+    include_str!("fixtures/note_id_max.json"),
+    include_str!("fixtures/negative_timestamp.json"),
+];
+
+fn note_pda(user: &Pubkey, note_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"note", user.as_ref(), &note_id.to_le_bytes()], &sol_ar_backend::ID)
+}
+
+fn genesis_revision_pda(note_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"rev", note_account.as_ref(), &0u64.to_le_bytes()], &sol_ar_backend::ID)
+}
+
+#[test]
+fn note_account_space_matches_init_space() {
+    for raw in FIXTURES {
+        let vector = parse_vector(raw);
+        assert_eq!(
+            8 + NoteAccount::INIT_SPACE,
+            vector.expected_note_account_space,
+            "declared space for NoteAccount drifted from the fixture"
+        );
+    }
+}
+
+#[test]
+fn pda_derivation_matches_vectors() {
+    for raw in FIXTURES {
+        let vector = parse_vector(raw);
+        let user: Pubkey = vector.user.parse().unwrap();
+
+        let (note, note_bump) = note_pda(&user, vector.note_id);
+        assert_eq!(note.to_string(), vector.expected_note_pda);
+        assert_eq!(note_bump, vector.expected_note_bump);
+
+        let (revision, revision_bump) = genesis_revision_pda(&note);
+        assert_eq!(revision.to_string(), vector.expected_genesis_revision_pda);
+        assert_eq!(revision_bump, vector.expected_genesis_revision_bump);
+    }
+}
+
+#[test]
+fn initialize_note_produces_byte_identical_account_state() {
+    for raw in FIXTURES {
+        let vector = parse_vector(raw);
+        let user: Pubkey = vector.user.parse().unwrap();
+        let (note, _) = note_pda(&user, vector.note_id);
+        let (genesis_revision, _) = genesis_revision_pda(&note);
+
+        // The instruction itself is not executed here: the account is built with the same
+        // field values `initialize_note` (via `init_genesis_revision`) assigns, then serialized.
+        let note_account = NoteAccount {
+            owner: user,
+            note_id: vector.note_id,
+            arweave_hash: vector.arweave_hash.clone(),
+            timestamp: vector.timestamp,
+            is_permanent: false,
+            latest_revision: genesis_revision,
+            revision_count: 1,
+            pending_owner: None,
+            encryption: None,
+            key_epoch: 0,
+        };
+
+        let mut bytes = NoteAccount::DISCRIMINATOR.to_vec();
+        note_account.serialize(&mut bytes).unwrap();
+        assert_eq!(to_hex(&bytes), vector.expected_note_account_bytes_hex);
+    }
+}